@@ -0,0 +1,578 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use crate::CliphistError;
+
+/// Which clipboard a provider operation targets.
+///
+/// `Selection` is the X11/Wayland "primary selection" (middle-click paste);
+/// not every provider can address it independently of the regular clipboard.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardType {
+    Clipboard,
+    #[serde(alias = "primary")]
+    Selection,
+}
+
+/// A clipboard backend capable of writing and reading the system clipboard.
+///
+/// Concrete implementations wrap a specific command-line tool (`wl-copy`,
+/// `xclip`, ...); detection picks one based on the running environment, but
+/// callers can also supply a custom provider loaded from config.
+pub trait ClipboardProvider: Send + Sync {
+    /// Human-readable identifier for diagnostics, e.g. "wl-copy+wl-paste".
+    fn name(&self) -> String;
+    fn set_contents(&self, text: &str, selection: ClipboardType) -> Result<(), CliphistError>;
+    fn get_contents(&self, selection: ClipboardType) -> Result<String, CliphistError>;
+
+    /// Whether this provider can address the primary selection independently
+    /// of the regular clipboard. Defaults to true; providers backed by tools
+    /// with no such concept override this to false.
+    fn supports_selection(&self) -> bool {
+        true
+    }
+
+    /// Sets non-UTF-8 clipboard content (e.g. an image) tagged with `mime`.
+    /// Providers that can't address an explicit MIME type return an error.
+    fn set_contents_bytes(&self, _data: &[u8], _mime: &str) -> Result<(), CliphistError> {
+        Err(CliphistError {
+            message: format!("{} does not support setting binary clipboard content", self.name()),
+        })
+    }
+}
+
+fn selection_unsupported(provider: &str) -> CliphistError {
+    CliphistError {
+        message: format!("{} does not support the primary selection", provider),
+    }
+}
+
+fn binary_exists(bin: &str) -> bool {
+    which::which(bin).is_ok()
+}
+
+fn run_pipe(command: &str, args: &[&str], input: &str) -> Result<(), CliphistError> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| CliphistError {
+            message: format!("Failed to execute {}: {}", command, e),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(input.as_bytes()) {
+            let _ = child.wait();
+            return Err(e.into());
+        }
+    }
+
+    let status = child.wait().map_err(|e| CliphistError {
+        message: format!("Failed to wait for {}: {}", command, e),
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(CliphistError {
+            message: format!("{} exited with a failure status", command),
+        })
+    }
+}
+
+fn run_pipe_bytes(command: &str, args: &[&str], input: &[u8]) -> Result<(), CliphistError> {
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| CliphistError {
+            message: format!("Failed to execute {}: {}", command, e),
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(input) {
+            let _ = child.wait();
+            return Err(e.into());
+        }
+    }
+
+    let status = child.wait().map_err(|e| CliphistError {
+        message: format!("Failed to wait for {}: {}", command, e),
+    })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(CliphistError {
+            message: format!("{} exited with a failure status", command),
+        })
+    }
+}
+
+fn run_capture(command: &str, args: &[&str]) -> Result<String, CliphistError> {
+    let output = Command::new(command).args(args).output().map_err(|e| CliphistError {
+        message: format!("Failed to execute {}: {}", command, e),
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(CliphistError {
+            message: format!("{} exited with a failure status", command),
+        })
+    }
+}
+
+struct WaylandProvider;
+
+impl ClipboardProvider for WaylandProvider {
+    fn name(&self) -> String {
+        "wl-copy+wl-paste".to_string()
+    }
+
+    fn set_contents(&self, text: &str, selection: ClipboardType) -> Result<(), CliphistError> {
+        let args: &[&str] = match selection {
+            ClipboardType::Clipboard => &[],
+            ClipboardType::Selection => &["--primary"],
+        };
+        run_pipe("wl-copy", args, text)
+    }
+
+    fn get_contents(&self, selection: ClipboardType) -> Result<String, CliphistError> {
+        let args: &[&str] = match selection {
+            ClipboardType::Clipboard => &["--no-newline"],
+            ClipboardType::Selection => &["--no-newline", "--primary"],
+        };
+        run_capture("wl-paste", args)
+    }
+
+    fn set_contents_bytes(&self, data: &[u8], mime: &str) -> Result<(), CliphistError> {
+        run_pipe_bytes("wl-copy", &["--type", mime], data)
+    }
+}
+
+struct XclipProvider;
+
+impl ClipboardProvider for XclipProvider {
+    fn name(&self) -> String {
+        "xclip".to_string()
+    }
+
+    fn set_contents(&self, text: &str, selection: ClipboardType) -> Result<(), CliphistError> {
+        let target = match selection {
+            ClipboardType::Clipboard => "clipboard",
+            ClipboardType::Selection => "primary",
+        };
+        run_pipe("xclip", &["-selection", target], text)
+    }
+
+    fn get_contents(&self, selection: ClipboardType) -> Result<String, CliphistError> {
+        let target = match selection {
+            ClipboardType::Clipboard => "clipboard",
+            ClipboardType::Selection => "primary",
+        };
+        run_capture("xclip", &["-selection", target, "-o"])
+    }
+
+    fn set_contents_bytes(&self, data: &[u8], mime: &str) -> Result<(), CliphistError> {
+        run_pipe_bytes("xclip", &["-selection", "clipboard", "-t", mime], data)
+    }
+}
+
+struct XselProvider;
+
+impl ClipboardProvider for XselProvider {
+    fn name(&self) -> String {
+        "xsel".to_string()
+    }
+
+    fn set_contents(&self, text: &str, selection: ClipboardType) -> Result<(), CliphistError> {
+        let flag = match selection {
+            ClipboardType::Clipboard => "-b",
+            ClipboardType::Selection => "-p",
+        };
+        run_pipe("xsel", &[flag, "--input"], text)
+    }
+
+    fn get_contents(&self, selection: ClipboardType) -> Result<String, CliphistError> {
+        let flag = match selection {
+            ClipboardType::Clipboard => "-b",
+            ClipboardType::Selection => "-p",
+        };
+        run_capture("xsel", &[flag, "--output"])
+    }
+}
+
+struct Win32YankProvider;
+
+impl ClipboardProvider for Win32YankProvider {
+    fn name(&self) -> String {
+        "win32yank".to_string()
+    }
+
+    fn set_contents(&self, text: &str, _selection: ClipboardType) -> Result<(), CliphistError> {
+        run_pipe("win32yank.exe", &["-i"], text)
+    }
+
+    fn get_contents(&self, _selection: ClipboardType) -> Result<String, CliphistError> {
+        run_capture("win32yank.exe", &["-o"])
+    }
+
+    fn supports_selection(&self) -> bool {
+        false
+    }
+}
+
+struct MacOsProvider;
+
+impl ClipboardProvider for MacOsProvider {
+    fn name(&self) -> String {
+        "pbcopy+pbpaste".to_string()
+    }
+
+    fn set_contents(&self, text: &str, _selection: ClipboardType) -> Result<(), CliphistError> {
+        run_pipe("pbcopy", &[], text)
+    }
+
+    fn get_contents(&self, _selection: ClipboardType) -> Result<String, CliphistError> {
+        run_capture("pbpaste", &[])
+    }
+
+    fn supports_selection(&self) -> bool {
+        false
+    }
+}
+
+struct TermuxProvider;
+
+impl ClipboardProvider for TermuxProvider {
+    fn name(&self) -> String {
+        "termux-clipboard".to_string()
+    }
+
+    fn set_contents(&self, text: &str, _selection: ClipboardType) -> Result<(), CliphistError> {
+        run_pipe("termux-clipboard-set", &[], text)
+    }
+
+    fn get_contents(&self, _selection: ClipboardType) -> Result<String, CliphistError> {
+        run_capture("termux-clipboard-get", &[])
+    }
+
+    fn supports_selection(&self) -> bool {
+        false
+    }
+}
+
+/// A user-defined provider backed by arbitrary yank/paste commands, mirroring
+/// the `clipboard-provider` + custom `{command, args}` config model.
+pub struct CustomProvider {
+    pub yank_command: String,
+    pub yank_args: Vec<String>,
+    pub paste_command: String,
+    pub paste_args: Vec<String>,
+}
+
+impl ClipboardProvider for CustomProvider {
+    fn name(&self) -> String {
+        format!("custom:{}", self.yank_command)
+    }
+
+    fn set_contents(&self, text: &str, _selection: ClipboardType) -> Result<(), CliphistError> {
+        let args: Vec<&str> = self.yank_args.iter().map(String::as_str).collect();
+        run_pipe(&self.yank_command, &args, text)
+    }
+
+    fn get_contents(&self, _selection: ClipboardType) -> Result<String, CliphistError> {
+        let args: Vec<&str> = self.paste_args.iter().map(String::as_str).collect();
+        run_capture(&self.paste_command, &args)
+    }
+
+    fn supports_selection(&self) -> bool {
+        false
+    }
+}
+
+/// Config shape for a user-supplied custom clipboard provider, mirroring the
+/// `clipboard-provider` + custom `{command, args}` model. It is passed in
+/// through the `configure_clipboard_provider` command; no config file is read.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CustomClipboardConfig {
+    pub yank_command: String,
+    pub yank_args: Vec<String>,
+    pub paste_command: String,
+    pub paste_args: Vec<String>,
+}
+
+fn is_wsl() -> bool {
+    std::fs::read_to_string("/proc/version")
+        .map(|v| v.to_lowercase().contains("microsoft"))
+        .unwrap_or(false)
+}
+
+fn is_tmux() -> bool {
+    env::var("TMUX").is_ok()
+}
+
+/// Reads a variable from the tmux server's global environment. A tmux
+/// session attached from a different pane/SSH connection than the one that
+/// started it often doesn't inherit `DISPLAY`/`WAYLAND_DISPLAY` in its own
+/// process environment even though the server-wide value is still set.
+fn tmux_global_env(var: &str) -> Option<String> {
+    let output = Command::new("tmux").args(["show-environment", "-g", var]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix(&format!("{}=", var)))
+        .map(str::to_string)
+}
+
+/// Resolves a display-related env var, falling back to tmux's global
+/// environment when running inside a tmux session (see `tmux_global_env`).
+fn display_var(var: &str) -> Option<String> {
+    env::var(var).ok().or_else(|| if is_tmux() { tmux_global_env(var) } else { None })
+}
+
+/// Identifies a provider kind for the purpose of priority ordering, kept
+/// separate from the `Box<dyn ClipboardProvider>` instances so the ordering
+/// decision in `pick_order` stays pure and testable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProviderKind {
+    Wayland,
+    Xclip,
+    Xsel,
+    Win32Yank,
+    MacOs,
+    Termux,
+}
+
+/// Pure priority decision: given which environment signals and binaries are
+/// present, returns the provider kinds to try, in order (Wayland > xclip >
+/// xsel > win32yank > macOS > termux). Contains no I/O so the ordering can be
+/// asserted directly in tests instead of only by manual inspection.
+fn pick_order(
+    wayland_display: bool,
+    wl_copy: bool,
+    wl_paste: bool,
+    display: bool,
+    xclip: bool,
+    xsel: bool,
+    wsl: bool,
+    win32yank: bool,
+    macos: bool,
+    pbcopy: bool,
+    pbpaste: bool,
+    termux_env: bool,
+    termux_set: bool,
+) -> Vec<ProviderKind> {
+    let mut order = Vec::new();
+
+    if wayland_display && wl_copy && wl_paste {
+        order.push(ProviderKind::Wayland);
+    }
+    if display {
+        if xclip {
+            order.push(ProviderKind::Xclip);
+        }
+        if xsel {
+            order.push(ProviderKind::Xsel);
+        }
+    }
+    if wsl && win32yank {
+        order.push(ProviderKind::Win32Yank);
+    }
+    if macos && pbcopy && pbpaste {
+        order.push(ProviderKind::MacOs);
+    }
+    if termux_env && termux_set {
+        order.push(ProviderKind::Termux);
+    }
+
+    order
+}
+
+fn provider_for_kind(kind: ProviderKind) -> Box<dyn ClipboardProvider> {
+    match kind {
+        ProviderKind::Wayland => Box::new(WaylandProvider),
+        ProviderKind::Xclip => Box::new(XclipProvider),
+        ProviderKind::Xsel => Box::new(XselProvider),
+        ProviderKind::Win32Yank => Box::new(Win32YankProvider),
+        ProviderKind::MacOs => Box::new(MacOsProvider),
+        ProviderKind::Termux => Box::new(TermuxProvider),
+    }
+}
+
+/// Builds the ordered list of providers whose backing binaries actually
+/// resolve on this system, in priority order. Resolution happens up front via
+/// `which` so a missing binary is never mistaken for a runtime failure.
+fn candidate_providers() -> Vec<Box<dyn ClipboardProvider>> {
+    let order = pick_order(
+        display_var("WAYLAND_DISPLAY").is_some(),
+        binary_exists("wl-copy"),
+        binary_exists("wl-paste"),
+        display_var("DISPLAY").is_some(),
+        binary_exists("xclip"),
+        binary_exists("xsel"),
+        is_wsl(),
+        binary_exists("win32yank.exe"),
+        cfg!(target_os = "macos"),
+        binary_exists("pbcopy"),
+        binary_exists("pbpaste"),
+        env::var("TERMUX_VERSION").is_ok(),
+        binary_exists("termux-clipboard-set"),
+    );
+
+    order.into_iter().map(provider_for_kind).collect()
+}
+
+static PROVIDERS: Mutex<Option<Vec<Box<dyn ClipboardProvider>>>> = Mutex::new(None);
+
+fn cached_providers() -> std::sync::MutexGuard<'static, Option<Vec<Box<dyn ClipboardProvider>>>> {
+    let mut guard = PROVIDERS.lock().unwrap();
+    if guard.is_none() {
+        *guard = Some(candidate_providers());
+    }
+    guard
+}
+
+/// Runs `f` against each resolved candidate provider in priority order,
+/// falling through to the next one whenever `f` returns an error: a resolved
+/// binary failing at runtime, or an operation the provider does not support
+/// (primary selection, binary content). Returns a clear error if no
+/// candidate resolved at all.
+fn with_provider<T>(f: impl Fn(&dyn ClipboardProvider) -> Result<T, CliphistError>) -> Result<T, CliphistError> {
+    let guard = cached_providers();
+    let providers = guard.as_ref().unwrap();
+
+    if providers.is_empty() {
+        return Err(CliphistError {
+            message: "No clipboard tool available. Install wl-clipboard, xclip, xsel, win32yank, \
+                      or configure a custom clipboard-provider."
+                .to_string(),
+        });
+    }
+
+    let mut last_err = None;
+    for provider in providers {
+        match f(provider.as_ref()) {
+            Ok(value) => return Ok(value),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap())
+}
+
+/// Overrides the cached provider with a user-supplied custom one. Called from
+/// the `configure_clipboard_provider` command; the override lasts until the
+/// app exits and is not persisted.
+pub fn set_custom_provider(config: CustomClipboardConfig) {
+    *PROVIDERS.lock().unwrap() = Some(vec![Box::new(CustomProvider {
+        yank_command: config.yank_command,
+        yank_args: config.yank_args,
+        paste_command: config.paste_command,
+        paste_args: config.paste_args,
+    })]);
+}
+
+/// Returns the name of the clipboard tool currently selected for use, for
+/// diagnostics (e.g. so the frontend can warn when nothing is available).
+pub fn provider_name() -> Result<String, CliphistError> {
+    let guard = cached_providers();
+    guard
+        .as_ref()
+        .unwrap()
+        .first()
+        .map(|provider| provider.name())
+        .ok_or_else(|| CliphistError {
+            message: "No clipboard tool available. Install wl-clipboard, xclip, xsel, win32yank, \
+                      or configure a custom clipboard-provider."
+                .to_string(),
+        })
+}
+
+fn check_selection_support(provider: &dyn ClipboardProvider, selection: ClipboardType) -> Result<(), CliphistError> {
+    if selection == ClipboardType::Selection && !provider.supports_selection() {
+        return Err(selection_unsupported(&provider.name()));
+    }
+    Ok(())
+}
+
+pub fn copy_to_clipboard(text: &str, selection: ClipboardType) -> Result<(), CliphistError> {
+    with_provider(|provider| {
+        check_selection_support(provider, selection)?;
+        provider.set_contents(text, selection)
+    })
+}
+
+pub fn paste_from_clipboard(selection: ClipboardType) -> Result<String, CliphistError> {
+    with_provider(|provider| {
+        check_selection_support(provider, selection)?;
+        provider.get_contents(selection)
+    })
+}
+
+pub fn copy_bytes_to_clipboard(data: &[u8], mime: &str) -> Result<(), CliphistError> {
+    with_provider(|provider| provider.set_contents_bytes(data, mime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wayland_is_preferred_over_x11_when_both_available() {
+        let order = pick_order(true, true, true, true, true, true, false, false, false, false, false, false, false);
+        assert_eq!(order, vec![ProviderKind::Wayland, ProviderKind::Xclip, ProviderKind::Xsel]);
+    }
+
+    #[test]
+    fn xclip_is_preferred_over_xsel() {
+        let order = pick_order(false, false, false, true, true, true, false, false, false, false, false, false, false);
+        assert_eq!(order, vec![ProviderKind::Xclip, ProviderKind::Xsel]);
+    }
+
+    #[test]
+    fn xsel_used_when_xclip_missing() {
+        let order = pick_order(false, false, false, true, false, true, false, false, false, false, false, false, false);
+        assert_eq!(order, vec![ProviderKind::Xsel]);
+    }
+
+    #[test]
+    fn full_priority_order_when_everything_available() {
+        let order =
+            pick_order(true, true, true, true, true, true, true, true, true, true, true, true, true);
+        assert_eq!(
+            order,
+            vec![
+                ProviderKind::Wayland,
+                ProviderKind::Xclip,
+                ProviderKind::Xsel,
+                ProviderKind::Win32Yank,
+                ProviderKind::MacOs,
+                ProviderKind::Termux,
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_binaries_are_excluded_even_when_env_signals_are_present() {
+        let order = pick_order(
+            true, false, false, true, false, false, true, false, true, false, false, true, false,
+        );
+        assert!(order.is_empty());
+    }
+
+    #[test]
+    fn nothing_detected_returns_empty_order() {
+        let order = pick_order(
+            false, false, false, false, false, false, false, false, false, false, false, false, false,
+        );
+        assert!(order.is_empty());
+    }
+}
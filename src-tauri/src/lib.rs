@@ -1,11 +1,17 @@
 use serde::{Deserialize, Serialize};
 use std::process::Command;
 
+mod clipboard;
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ClipboardEntry {
     pub id: String,
     pub content: String,
     pub content_type: String,
+    /// MIME type for "image" entries (e.g. "image/png"), so the frontend
+    /// knows what to pass to `copy_entry_as_image`; None for "text" and
+    /// "binary" entries.
+    pub mime_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -74,6 +80,37 @@ fn fuzzy_match(content: &str, query: &str) -> bool {
     true
 }
 
+const IMAGE_FORMATS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff"];
+
+fn image_mime_type(format: &str) -> &'static str {
+    match format {
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "tiff" => "image/tiff",
+        _ => "image/png",
+    }
+}
+
+// cliphist emits placeholder lines for binary payloads, e.g.
+// "[[ binary data 3.8 MiB png 1920x1080 ]]". Detect those to set
+// content_type/mime_type instead of assuming every entry is text.
+fn detect_content(content: &str) -> (&'static str, Option<String>) {
+    if !content.starts_with("[[ binary data") {
+        return ("text", None);
+    }
+
+    let lower = content.to_lowercase();
+    for format in IMAGE_FORMATS {
+        if lower.split_whitespace().any(|word| word == *format) {
+            return ("image", Some(image_mime_type(format).to_string()));
+        }
+    }
+
+    ("binary", None)
+}
+
 fn parse_cliphist_list(output: &str) -> Result<Vec<ClipboardEntry>, CliphistError> {
     let mut entries = Vec::new();
 
@@ -83,11 +120,13 @@ fn parse_cliphist_list(output: &str) -> Result<Vec<ClipboardEntry>, CliphistErro
             let id = parts[0].to_string();
             // Join all remaining parts as content (in case content contains tabs)
             let content = parts[1..].join("\t");
+            let (content_type, mime_type) = detect_content(&content);
 
             entries.push(ClipboardEntry {
                 id,
                 content: content.clone(), // Full content for display
-                content_type: "text".to_string(), // Assume text for now
+                content_type: content_type.to_string(),
+                mime_type,
             });
         }
     }
@@ -113,6 +152,44 @@ fn get_entry_content(id: String) -> Result<String, CliphistError> {
     run_cliphist_command(&["decode", &id])
 }
 
+/// Runs `cliphist decode <id>` and returns the raw decoded bytes, shared by
+/// the commands that need an entry's payload instead of its text preview.
+fn decode_raw(id: &str) -> Result<Vec<u8>, CliphistError> {
+    let output = Command::new("cliphist")
+        .args(["decode", id])
+        .output()
+        .map_err(|e| CliphistError {
+            message: format!("Failed to execute cliphist: {}. Make sure cliphist is installed.", e),
+        })?;
+
+    if !output.status.success() {
+        return Err(CliphistError {
+            message: format!("cliphist command failed: {}", String::from_utf8_lossy(&output.stderr)),
+        });
+    }
+
+    Ok(output.stdout)
+}
+
+/// Decodes a binary/image entry and returns it base64-encoded so the
+/// frontend can render a preview (e.g. as a data: URL) without assuming the
+/// payload is valid UTF-8 text.
+#[tauri::command]
+fn decode_entry(id: String) -> Result<String, CliphistError> {
+    use base64::Engine;
+
+    let bytes = decode_raw(&id)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// Decodes a binary/image entry and copies it back to the clipboard using
+/// the provider subsystem's non-UTF-8 path, tagged with its MIME type.
+#[tauri::command]
+fn copy_entry_as_image(id: String, mime_type: String) -> Result<(), CliphistError> {
+    let bytes = decode_raw(&id)?;
+    clipboard::copy_bytes_to_clipboard(&bytes, &mime_type)
+}
+
 #[tauri::command]
 fn delete_entry(id: String) -> Result<(), CliphistError> {
     use std::io::Write;
@@ -157,42 +234,68 @@ fn search_history(query: String) -> Result<Vec<ClipboardEntry>, CliphistError> {
 }
 
 #[tauri::command]
-fn copy_to_clipboard(content: String) -> Result<(), CliphistError> {
-    use std::io::Write;
+fn copy_to_clipboard(content: String, selection: Option<clipboard::ClipboardType>) -> Result<(), CliphistError> {
+    clipboard::copy_to_clipboard(&content, selection.unwrap_or(clipboard::ClipboardType::Clipboard))
+}
 
-    // Try wl-copy first (Wayland)
-    if let Ok(mut child) = Command::new("wl-copy")
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-    {
-        if let Some(mut stdin) = child.stdin.take() {
-            let _ = stdin.write_all(content.as_bytes());
-        }
-        if child.wait().map(|s| s.success()).unwrap_or(false) {
-            return Ok(());
-        }
+#[tauri::command]
+fn paste_from_clipboard(selection: Option<clipboard::ClipboardType>) -> Result<String, CliphistError> {
+    clipboard::paste_from_clipboard(selection.unwrap_or(clipboard::ClipboardType::Clipboard))
+}
+
+#[tauri::command]
+fn configure_clipboard_provider(config: clipboard::CustomClipboardConfig) {
+    clipboard::set_custom_provider(config);
+}
+
+#[tauri::command]
+fn get_clipboard_provider() -> Result<String, CliphistError> {
+    clipboard::provider_name()
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_png_binary_entry() {
+        let (content_type, mime_type) = detect_content("[[ binary data 3.8 MiB png 1920x1080 ]]");
+        assert_eq!(content_type, "image");
+        assert_eq!(mime_type.as_deref(), Some("image/png"));
     }
 
-    // Try xclip (X11)
-    if let Ok(mut child) = Command::new("xclip")
-        .args(&["-selection", "clipboard"])
-        .stdin(std::process::Stdio::piped())
-        .spawn()
-    {
-        if let Some(mut stdin) = child.stdin.take() {
-            let _ = stdin.write_all(content.as_bytes());
-        }
-        if child.wait().map(|s| s.success()).unwrap_or(false) {
-            return Ok(());
+    #[test]
+    fn detects_other_image_formats() {
+        for (format, mime) in [
+            ("jpg", "image/jpeg"),
+            ("jpeg", "image/jpeg"),
+            ("gif", "image/gif"),
+            ("webp", "image/webp"),
+            ("tiff", "image/tiff"),
+        ] {
+            let content = format!("[[ binary data 120 KiB {} 640x480 ]]", format);
+            let (content_type, mime_type) = detect_content(&content);
+            assert_eq!(content_type, "image");
+            assert_eq!(mime_type.as_deref(), Some(mime));
         }
     }
 
-    Err(CliphistError {
-        message: "No clipboard tool available. Install wl-clipboard (Wayland) or xclip (X11).".to_string(),
-    })
-}
-
+    #[test]
+    fn detects_non_image_binary_entry() {
+        let (content_type, mime_type) = detect_content("[[ binary data 42 B application/pdf ]]");
+        assert_eq!(content_type, "binary");
+        assert_eq!(mime_type, None);
+    }
 
+    #[test]
+    fn detects_plain_text_entry() {
+        let (content_type, mime_type) = detect_content("just some copied text");
+        assert_eq!(content_type, "text");
+        assert_eq!(mime_type, None);
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -204,7 +307,12 @@ pub fn run() {
             get_entry_content,
             delete_entry,
             search_history,
-            copy_to_clipboard
+            copy_to_clipboard,
+            paste_from_clipboard,
+            configure_clipboard_provider,
+            get_clipboard_provider,
+            decode_entry,
+            copy_entry_as_image
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");